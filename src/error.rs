@@ -0,0 +1,71 @@
+// Copyright 2018-2020 ETH Zurich
+// Andreas Kurth <akurth@iis.ee.ethz.ch>
+// Gianluca Bellocchi <gianluca.bellocchi@unimore.it>
+//
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use std::fmt;
+
+/**********************************************************************************************
+*  Error
+*  ------------------------------------------------------------------------------------------
+*  Crate-local error type covering every way a conversion can fail. Variants that originate
+*  from a specific input line carry the file path and 1-based line number so a caller can
+*  report a precise diagnostic instead of an opaque panic backtrace.
+**********************************************************************************************/
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    BadLine { path: String, line: usize, text: String },
+    BadAddress { path: String, line: usize, text: String },
+    BadWordLength { path: String, line: usize, expected: usize, found: usize },
+    BadHexWord { path: String, line: usize, text: String },
+    DuplicateEntry { path: String, line: usize, addr: usize },
+    OverlappingSegment { path: String, addr: usize },
+    FormatString(strfmt::FmtError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "I/O error: {}", e),
+            Error::BadLine { path, line, text } =>
+                write!(f, "{}:{}: expected an '<address> <data>' line, found '{}'", path, line, text),
+            Error::BadAddress { path, line, text } =>
+                write!(f, "{}:{}: invalid hexadecimal address or index '{}'", path, line, text),
+            Error::BadWordLength { path, line, expected, found } =>
+                write!(f, "{}:{}: expected an {}-hex-digit data word, found {} digits",
+                    path, line, expected, found),
+            Error::BadHexWord { path, line, text } =>
+                write!(f, "{}:{}: expected a hexadecimal data word, found '{}'", path, line, text),
+            Error::DuplicateEntry { path, line, addr } =>
+                write!(f, "{}:{}: duplicate entry for address 0x{:x}", path, line, addr),
+            Error::OverlappingSegment { path, addr } =>
+                write!(f, "{}: overlapping ELF segment at address 0x{:x}", path, addr),
+            Error::FormatString(e) => write!(f, "format string error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Error {
+        Error::Io(e)
+    }
+}
+
+impl From<strfmt::FmtError> for Error {
+    fn from(e: strfmt::FmtError) -> Error {
+        Error::FormatString(e)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;